@@ -1,51 +1,136 @@
-use std::{char::ParseCharError, fs, num::ParseIntError, str::FromStr};
+use std::{fmt, fs, num::ParseIntError, str::FromStr};
 
 static INPUT_FILE: &str = "input.txt";
 
-fn main() {
-    let expected_string =format!("Failed to open file '{INPUT_FILE}'"); 
-    let contents = 
-        fs::read_to_string(INPUT_FILE).expect(&expected_string);
+fn main() -> Result<(), Error> {
+    let contents = fs::read_to_string(INPUT_FILE)?;
 
     // This splits the input into two parts, the text before the blank
     // line (`stack_config`) and the part after the blank line (`instructions`).
     let (stack_config, instructions) = contents
         .split_once("\n\n")
-        .expect("There was no blank line in the input");
+        .ok_or(Error::MissingBlankLine)?;
 
     // The `.parse()` call actually calls the appropriate `from_str()`, which
     // in this case is in the `impl FromStr for Stacks` block.
-    let stacks: Stacks = stack_config
-        .parse()
-        .expect("Failed to parse stack configuration");
+    let stacks: Stacks = stack_config.parse()?;
 
     // This `.parse()` call uses the implementation of `from_str()`
     // in the `impl FromStr for CraneInstructions` block.
-    let instructions: CraneInstructions = instructions
-        .parse()
-        .expect("Failed to parse crane instructions");
+    let instructions: CraneInstructions = instructions.parse()?;
 
-    // Run all the instructions, returning the final `Stacks` state.
-    let final_state = stacks
-        .apply_instructions(&instructions)
-        .expect("Applying an instruction set failed");
+    // Run all the instructions, returning the final `Stacks` state. This is
+    // `part2`, so the crane is a CrateMover 9001 that moves whole groups of
+    // crates without reversing them.
+    let final_state = stacks.apply_instructions(&instructions, CraneModel::Mover9001)?;
 
     // Get the top of the stacks and print that out.
-    println!(
-        "The top of the stacks is {}",
-        final_state
-            .tops_string()
-            .expect("Tried to take the top of an empty stack")
-    );
+    println!("The top of the stacks is {}", final_state.tops_string()?);
+
+    Ok(())
+}
+
+/// Everything that can go wrong running this program end to end, unified so
+/// `main` can propagate with `?` and still print one clean diagnostic instead
+/// of panicking on malformed input.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    MissingBlankLine,
+    Parse(ParseError),
+    Crane(CraneError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "could not read '{INPUT_FILE}': {err}"),
+            Error::MissingBlankLine => {
+                write!(f, "input has no blank line separating the stacks from the instructions")
+            }
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::Crane(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::MissingBlankLine => None,
+            Error::Parse(err) => Some(err),
+            Error::Crane(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<CraneError> for Error {
+    fn from(err: CraneError) -> Self {
+        Error::Crane(err)
+    }
+}
+
+/// A 1-based line and column at which a parse error was detected. Lines are
+/// counted from the start of the string handed to `parse()`, so for the
+/// instruction block they start at its first line, not at the top of the
+/// input file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLocation {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for ParseLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, col {}", self.line, self.col)
+    }
 }
 
 #[derive(Debug)]
 pub enum ParseError {
     // Add different variants as you discover different kinds of parsing errors.
     // This could include things like too many stacks, illegal strings on a stack, etc.
-    InvalidId(String),
-    InvalidChar(String),
-    InvalidInstruction(String),
+    // Each variant carries the `ParseLocation` at which parsing gave up, plus a
+    // message. The instruction/footer variants also carry the underlying
+    // `ParseIntError` when the failure was a bad number, so it can be chained
+    // as this error's `source()`.
+    InvalidChar(ParseLocation, String),
+    InvalidInstruction(ParseLocation, String, Option<ParseIntError>),
+    InvalidFooter(ParseLocation, String, Option<ParseIntError>),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar(loc, msg) => write!(f, "{loc}: {msg}"),
+            ParseError::InvalidInstruction(loc, msg, _) => write!(f, "{loc}: {msg}"),
+            ParseError::InvalidFooter(loc, msg, _) => write!(f, "{loc}: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::InvalidInstruction(_, _, Some(err)) | ParseError::InvalidFooter(_, _, Some(err)) => {
+                Some(err)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -54,7 +139,7 @@ pub struct Stacks {
 }
 
 #[derive(Debug)]
-enum CraneError {
+pub enum CraneError {
     // Add different variants as you discover different kinds of errors
     // that can occur when applying a crane instruction.
     // This could include things like trying to move from an empty stack,
@@ -63,61 +148,102 @@ enum CraneError {
     IndexError(String)
 }
 
+impl fmt::Display for CraneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraneError::EmptyStack(msg) | CraneError::IndexError(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CraneError {}
+
+/// Which crane is doing the moving, since the two models rearrange crates
+/// differently even when given the same instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraneModel {
+    /// Moves crates one at a time, so a multi-crate move reverses their order.
+    Mover9000,
+    /// Moves a whole group of crates at once, preserving their order.
+    Mover9001,
+}
+
 impl Stacks {
-    /// Apply a single instruction to the set of stacks in `self`.
+    /// Apply a single instruction to the set of stacks in `self`, using the
+    /// given `model` to decide whether multi-crate moves reverse order.
     /// Return the new set of stacks, or a `CraneError` if the instruction
     /// is invalid.
-    fn apply_instruction(mut self, instruction: &CraneInstruction) -> Result<Self, CraneError> {
-        let mut tmp_stack: Vec<char> = Vec::new();
-
+    fn apply_instruction(
+        mut self,
+        instruction: &CraneInstruction,
+        model: CraneModel,
+    ) -> Result<Self, CraneError> {
         //Find the indeces of the relevant stacks considering 0 vs 1 based indexing.
-        let start_index: usize = instruction.from_stack - 1;
-        let end_index: usize = instruction.to_stack - 1;
+        let start_index: usize = instruction.from_stack.checked_sub(1).ok_or_else(|| {
+            CraneError::IndexError(std::format!(
+                "Stack indices are 1-based; {} is not a valid \"from\" stack.",
+                instruction.from_stack
+            ))
+        })?;
+        let end_index: usize = instruction.to_stack.checked_sub(1).ok_or_else(|| {
+            CraneError::IndexError(std::format!(
+                "Stack indices are 1-based; {} is not a valid \"to\" stack.",
+                instruction.to_stack
+            ))
+        })?;
         let move_num: usize = instruction.num_to_move;
-        
+
 
         //Process the starting stack as needed.
-        let mut active_stack: &mut Stack = match self.stacks.get_mut(start_index) {
+        let active_stack: &mut Stack = match self.stacks.get_mut(start_index) {
             Some(s) => s,
             None => {
                 return Err(CraneError::IndexError(std::format!("Could not find a stack at index {start_index}.")))
             }
         };
 
-        if move_num > active_stack.len() {
+        if !active_stack.has(move_num) {
             return Err(CraneError::EmptyStack(std::format!(
                 "Cannot take {count} items from stack {start}, since stack {start} only has {start_count} items.\n
-                From stack: {from_stack:?}", 
-                count=move_num, 
-                start=instruction.from_stack, 
+                From stack: {from_stack:?}",
+                count=move_num,
+                start=instruction.from_stack,
                 start_count=active_stack.len(),
                 from_stack=active_stack
             )));
         }
 
-        for _ in 0..move_num {
-            tmp_stack.push(active_stack.pop());
+        // `pop_n` always returns the moved crates bottom-to-top; `has`
+        // just checked there are at least `move_num`, so this can't fail.
+        let mut moved: Vec<char> = active_stack.pop_n(move_num).expect("checked by has() above");
+
+        // Mover 9000 picks crates up one at a time, so it reverses their order.
+        if model == CraneModel::Mover9000 {
+            moved.reverse();
         }
 
         //Process the end stacks.
-        active_stack = match self.stacks.get_mut(end_index) {
+        let dst_stack: &mut Stack = match self.stacks.get_mut(end_index) {
             Some(s) => s,
             None => {
                 return Err(CraneError::IndexError(std::format!("Could not find stack at index {end_index}.")));
             }
         };
 
-        for _ in 0..move_num {
-            active_stack.push(tmp_stack.pop().unwrap());
-        }
+        dst_stack.push_n(moved);
 
         Ok(self)
     }
 
     /// Perform each of these instructions in order on the set of stacks
-    /// in `self`. Return the new set of stacks, or a `CraneError` if
+    /// in `self`, using the given `model` to decide how crates move.
+    /// Return the new set of stacks, or a `CraneError` if
     /// any of the instructions are invalid.
-    fn apply_instructions(self, instructions: &CraneInstructions) -> Result<Self, CraneError> {
+    fn apply_instructions(
+        self,
+        instructions: &CraneInstructions,
+        model: CraneModel,
+    ) -> Result<Self, CraneError> {
         let mut output: Result<Self, CraneError> = Ok(self);
 
         for instr in &instructions.instructions {
@@ -125,7 +251,7 @@ impl Stacks {
                 Ok(s) => s,
                 Err(err) => { return Err(err); }
             };
-            output = tmp.apply_instruction(instr);
+            output = tmp.apply_instruction(instr, model);
 
         }
 
@@ -148,23 +274,17 @@ impl Stacks {
 impl FromStr for Stacks {
     type Err = ParseError;
 
-    // You probably want to use `s.lines()` to create an iterator over the lines (one per stack).
-    // Then for each line:
-    //   (a) extract the number at the front as the stack number
-    //   (b) extract the following characters as the stack contents
-    // The function `split_ascii_whitespace()` should prove useful.
-    // Note that the stack numbers start at 1 and you'll need the indices
-    // in `Stacks::stacks` to start at 0.
+    // Parses the canonical AoC drawing, e.g.
+    //     [D]
+    // [N] [C]
+    // [Z] [M] [P]
+    //  1   2   3
+    // The grammar itself (a crate cell, a row of cells, the footer) lives
+    // in the `parsing` module; this just wires it up to `FromStr`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut output: Vec<Stack> = Vec::new();
-        for line in s.lines() {
-            let parse_result: Result<Stack, ParseError> = Stack::from_str(line);
-            match parse_result {
-                Ok(s) => { output.push(s); },
-                Err(err) => { return Err(err); }
-            }
-        }
-        Ok(Self { stacks: output }) 
+        Ok(Self {
+            stacks: parsing::parse_stacks(s)?,
+        })
     }
 }
 
@@ -197,35 +317,35 @@ impl Stack {
     pub fn get_last(&self) -> char {
         *self.stack.last().unwrap_or(&' ')
     }
-}
 
-impl FromStr for Stack {
-    type Err = ParseError;
+    /// Does this stack have at least `n` crates in it?
+    #[must_use]
+    pub fn has(&self, n: usize) -> bool {
+        self.stack.len() >= n
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut items: Vec<char> = Vec::new();
-        let mut _stack_id: usize = 0;
-        for (i, v) in s.split_ascii_whitespace().enumerate() {
-            if i == 0 {
-                let id_parse_result: Result<usize, ParseIntError> = v.parse();
-                _stack_id = match id_parse_result {
-                    Ok(s) => s,
-                    Err(_err) => { 
-                        return Err(ParseError::InvalidId(std::format!("Invalid character for stack id: {v}, expected single digit [0-9]")));
-                    }
-                }; 
-            } else {
-                let char_result: Result<char, ParseCharError> = v.parse();
-                let stack_char = match char_result {
-                    Ok(c) => c,
-                    Err(_err) => {
-                        return Err(ParseError::InvalidChar(std::format!("Invalid character for stack element: {v}, expected single character [A-Z].")));
-                    }
-                };
-                items.push(stack_char);
-            }
+    /// Look at the crate `from_top` positions down from the top, without
+    /// removing it. `from_top == 0` is the top crate.
+    #[must_use]
+    pub fn peek(&self, from_top: usize) -> Option<&char> {
+        let len = self.stack.len();
+        let index = len.checked_sub(from_top + 1)?;
+        self.stack.get(index)
+    }
+
+    /// Remove the top `n` crates, returning them in bottom-to-top order,
+    /// or `None` if the stack has fewer than `n` crates.
+    pub fn pop_n(&mut self, n: usize) -> Option<Vec<char>> {
+        if !self.has(n) {
+            return None;
         }
-        Ok(Self {stack: items })
+        let split_idx = self.stack.len() - n;
+        Some(self.stack.split_off(split_idx))
+    }
+
+    /// Push a batch of crates, given in bottom-to-top order.
+    pub fn push_n(&mut self, mut items: Vec<char>) {
+        self.stack.append(&mut items);
     }
 }
 
@@ -250,38 +370,14 @@ impl FromStr for CraneInstruction {
 
     // The instruction specification lines have the form
     //     move 13 from 8 to 7
-    // All we need to capture are the three numbers, which happen to
-    // be in the odd positions in the input line. I used a `filter` statement
-    // to extract those three items from the list, which I could
-    // then parse into `usize` using a `map` statement. You could also just
-    // "reach" into the split string directly if you find that easier.
+    // Delegates to `parsing::parse_instruction`; see that module for the grammar.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let slices: Vec<&str> = s.split_ascii_whitespace().collect();
-        let mut vals: Vec<usize> = Vec::new();
-
-        if slices.len() != 6 {
-            return Err(ParseError::InvalidInstruction("Invalid crane instruction length. Expected form: \"move <a> from <b> to <c>.\"".to_string()));
-        }
-
-        for (i, s) in slices.iter().enumerate() {
-            if i % 2 == 1 {
-                let parse_result: Result<usize, ParseIntError> = (*s).parse();
-                let v = match parse_result {
-                    Ok(v) => v,
-                    Err(_err) => {
-                        return Err(ParseError::InvalidInstruction(std::format!("Error parsing symbol {s}, expected single digit.")));
-                    }
-                };
-                vals.push(v); 
-            }
-        } 
-
-        let output: Self = Self { 
-            num_to_move: *vals.first().unwrap(), 
-            from_stack: *vals.get(1).unwrap(), 
-            to_stack: *vals.get(2).unwrap(), 
-        };
-        Ok(output)
+        let (num_to_move, from_stack, to_stack) = parsing::parse_instruction(s, 1)?;
+        Ok(Self {
+            num_to_move,
+            from_stack,
+            to_stack,
+        })
     }
 }
 
@@ -294,35 +390,215 @@ impl FromStr for CraneInstructions {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut instructions: Vec<CraneInstruction> = Vec::new();
-        for line in s.lines() {
-            let l = line.to_string();
-            let line_parts: Vec<&str> = l.split_ascii_whitespace().collect();
-            if line_parts.len() != 6 {
-                return Err(ParseError::InvalidInstruction("Invalid instruction format.".to_string()))
+        for (line_num, line) in (1..).zip(s.lines()) {
+            if !line.trim().is_empty() {
+                let (num_to_move, from_stack, to_stack) = parsing::parse_instruction(line, line_num)?;
+                instructions.push(CraneInstruction {
+                    num_to_move,
+                    from_stack,
+                    to_stack,
+                });
             }
+        }
+        Ok(Self { instructions })
+    }
+}
 
-            let move_count = match line_parts.get(1) {
-                Some(s) => s.parse::<usize>(),
-                None => {return Err(ParseError::InvalidInstruction("Invalid move count.".to_string())); }
-            };
+/// `nom`-based combinators for the AoC day 5 input format.
+///
+/// Pulling the grammar out of the `FromStr` impls lets each rule (a crate
+/// cell, a row of cells, an instruction) be composed and tested on its own,
+/// and lets a parse failure report *where* in the input it happened instead
+/// of just "invalid".
+mod parsing {
+    use std::num::ParseIntError;
+
+    use nom::branch::alt;
+    use nom::bytes::complete::tag;
+    use nom::character::complete::{char, digit1, satisfy, space0, space1};
+    use nom::combinator::{map, map_res, value};
+    use nom::error::{ErrorKind, FromExternalError};
+    use nom::multi::separated_list1;
+    use nom::sequence::{delimited, preceded, tuple};
+    use nom::IResult;
+
+    use super::{ParseError, ParseLocation, Stack};
+
+    /// A `nom` error type that, unlike the library's own `nom::error::Error`,
+    /// keeps hold of a `ParseIntError` from a failed `map_res(digit1, ...)` so
+    /// it can be forwarded as our own `ParseError`'s `source()`.
+    #[derive(Debug)]
+    struct NomError<'a> {
+        input: &'a str,
+        source: Option<ParseIntError>,
+    }
 
-            let orig_stack = match line_parts.get(3) {
-                Some(s) => s.parse::<usize>(),
-                None => {return Err(ParseError::InvalidInstruction("Invalid source stack.".to_string()));}
-            };
+    impl<'a> nom::error::ParseError<&'a str> for NomError<'a> {
+        fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+            NomError { input, source: None }
+        }
 
-            let target_stack = match line_parts.get(5) {
-                Some(s) => s.parse::<usize>(),
-                None => {return Err(ParseError::InvalidInstruction("Invalid target stack.".to_string()));}
-            };
+        fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+            other
+        }
+    }
 
-            instructions.push(CraneInstruction { 
-                num_to_move: move_count.unwrap(), 
-                from_stack: orig_stack.unwrap(), 
-                to_stack: target_stack.unwrap(), 
-            });
+    impl<'a> FromExternalError<&'a str, ParseIntError> for NomError<'a> {
+        fn from_external_error(input: &'a str, _kind: ErrorKind, source: ParseIntError) -> Self {
+            NomError { input, source: Some(source) }
         }
-        Ok(Self { instructions })
+    }
+
+    type PResult<'a, O> = IResult<&'a str, O, NomError<'a>>;
+
+    /// Matches a single crate slot: either `[X]` (a crate) or three spaces (an
+    /// empty slot in this row).
+    fn crate_cell(input: &str) -> PResult<'_, Option<char>> {
+        alt((
+            map(
+                delimited(char('['), satisfy(|c: char| c.is_ascii_uppercase()), char(']')),
+                Some,
+            ),
+            value(None, tag("   ")),
+        ))(input)
+    }
+
+    /// Matches a full row of crate slots, space-separated.
+    fn crate_row(input: &str) -> PResult<'_, Vec<Option<char>>> {
+        separated_list1(char(' '), crate_cell)(input)
+    }
+
+    /// Matches the footer row giving each stack's 1-based label, e.g. ` 1   2   3 `.
+    fn footer_row(input: &str) -> PResult<'_, Vec<usize>> {
+        delimited(
+            space0,
+            separated_list1(space1, map_res(digit1, str::parse::<usize>)),
+            space0,
+        )(input)
+    }
+
+    /// Transposes crate rows (top-to-bottom as written) into `Stack`s
+    /// (bottom-to-top), given the stack count read from the footer.
+    fn transpose(rows: &[Vec<Option<char>>], stack_count: usize) -> Vec<Stack> {
+        let mut stacks: Vec<Stack> = Vec::with_capacity(stack_count);
+        stacks.resize_with(stack_count, Stack::default);
+        for row in rows.iter().rev() {
+            for (stack, cell) in stacks.iter_mut().zip(row.iter()) {
+                if let Some(c) = cell {
+                    stack.push(*c);
+                }
+            }
+        }
+        stacks
+    }
+
+    /// Turns a `nom` parse failure on `line` (which is `line_num` in the
+    /// string being parsed, 1-based) into a `(location, message, source)` triple
+    /// that a `ParseError` variant can be built from.
+    fn describe_failure<'a>(
+        line_num: usize,
+        line: &'a str,
+        err: nom::Err<NomError<'a>>,
+    ) -> (ParseLocation, String, Option<ParseIntError>) {
+        match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                let col = line.len() - e.input.len() + 1;
+                let loc = ParseLocation { line: line_num, col };
+                match e.source {
+                    Some(source) => (loc, "expected a number".to_string(), Some(source)),
+                    None => (loc, format!("unexpected input {:?}", e.input), None),
+                }
+            }
+            nom::Err::Incomplete(_) => (
+                ParseLocation { line: line_num, col: line.len() + 1 },
+                "incomplete input".to_string(),
+                None,
+            ),
+        }
+    }
+
+    /// Parses the full stack-drawing block (crate rows plus a footer) into the
+    /// `Stack`s it describes.
+    pub(super) fn parse_stacks(input: &str) -> Result<Vec<Stack>, ParseError> {
+        let lines: Vec<(usize, &str)> = (1..)
+            .zip(input.lines())
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
+
+        let (footer_num, footer_line) = lines.last().copied().ok_or_else(|| {
+            ParseError::InvalidFooter(
+                ParseLocation { line: 1, col: 1 },
+                "Stack configuration has no footer line".to_string(),
+                None,
+            )
+        })?;
+
+        let (rest, labels) = footer_row(footer_line).map_err(|e| {
+            let (loc, msg, source) = describe_failure(footer_num, footer_line, e);
+            ParseError::InvalidFooter(loc, msg, source)
+        })?;
+
+        if !rest.trim().is_empty() {
+            return Err(ParseError::InvalidFooter(
+                ParseLocation { line: footer_num, col: footer_line.len() - rest.len() + 1 },
+                format!("unexpected trailing input {rest:?}"),
+                None,
+            ));
+        }
+
+        for (i, &label) in labels.iter().enumerate() {
+            if label != i + 1 {
+                return Err(ParseError::InvalidFooter(
+                    ParseLocation { line: footer_num, col: 1 },
+                    format!("Stack labels must be contiguous starting at 1, found {labels:?}"),
+                    None,
+                ));
+            }
+        }
+
+        let mut rows: Vec<Vec<Option<char>>> = Vec::with_capacity(lines.len());
+        for &(line_num, line) in &lines[..lines.len() - 1] {
+            let (rest, row) = crate_row(line).map_err(|e| {
+                let (loc, msg, _source) = describe_failure(line_num, line, e);
+                ParseError::InvalidChar(loc, msg)
+            })?;
+
+            if !rest.trim().is_empty() {
+                return Err(ParseError::InvalidChar(
+                    ParseLocation { line: line_num, col: line.len() - rest.len() + 1 },
+                    format!("unexpected trailing input {rest:?}"),
+                ));
+            }
+
+            rows.push(row);
+        }
+
+        Ok(transpose(&rows, labels.len()))
+    }
+
+    /// Parses a single `move <n> from <src> to <dst>` instruction line (which is
+    /// `line_num` in the string being parsed, 1-based) into `(count, source,
+    /// destination)`, all still 1-based.
+    pub(super) fn parse_instruction(input: &str, line_num: usize) -> Result<(usize, usize, usize), ParseError> {
+        let (rest, (count, src, dst)) = tuple((
+            preceded(tag("move "), map_res(digit1, str::parse::<usize>)),
+            preceded(tag(" from "), map_res(digit1, str::parse::<usize>)),
+            preceded(tag(" to "), map_res(digit1, str::parse::<usize>)),
+        ))(input)
+        .map_err(|e| {
+            let (loc, msg, source) = describe_failure(line_num, input, e);
+            ParseError::InvalidInstruction(loc, msg, source)
+        })?;
+
+        if !rest.trim().is_empty() {
+            return Err(ParseError::InvalidInstruction(
+                ParseLocation { line: line_num, col: input.len() - rest.len() + 1 },
+                format!("unexpected trailing input {rest:?}"),
+                None,
+            ));
+        }
+
+        Ok((count, src, dst))
     }
 }
 
@@ -334,13 +610,14 @@ impl FromStr for CraneInstructions {
 mod tests {
     use super::*;
 
-    // Test that we can parse stacks correctly.
+    // Test that we can parse the canonical AoC stack drawing, including rows
+    // whose empty slots in the third stack are written out as spaces.
     #[test]
     fn test_from_str() {
-        // The `\` at the end of the line escapes the newline and all following whitespace.
-        let input = "1 Z N\n\
-                           2 M C D\n\
-                           3 P";
+        let input = "    [D]    \n\
+                      [N] [C]    \n\
+                      [Z] [M] [P]\n\
+                       1   2   3 ";
         println!("{input}");
         #[allow(clippy::unwrap_used)]
         let stacks: Stacks = input.parse().unwrap();
@@ -354,6 +631,28 @@ mod tests {
         assert_eq!(stacks.stacks[2], vec!['P']);
     }
 
+    // Test that a footer with non-contiguous labels is rejected.
+    #[test]
+    fn test_from_str_rejects_bad_footer() {
+        let input = "[Z]\n 2 ";
+        assert!(input.parse::<Stacks>().is_err());
+    }
+
+    // Test that a parse error reports the line/column where it occurred.
+    #[test]
+    fn test_parse_error_reports_location() {
+        let Err(err) = "move x from 1 to 2".parse::<CraneInstruction>() else {
+            panic!("expected a parse error");
+        };
+        match err {
+            ParseError::InvalidInstruction(loc, _, _) => {
+                assert_eq!(loc, ParseLocation { line: 1, col: 6 });
+            }
+            other => panic!("expected InvalidInstruction, got {other:?}"),
+        }
+        assert_eq!(format!("{}", ParseLocation { line: 4, col: 9 }), "line 4, col 9");
+    }
+
     // Test that we can parse instructions correctly.
     #[test]
     fn test_instruction_parsing() {
@@ -375,10 +674,9 @@ mod tests {
     // instruction `move 2 from 0 to 1` moves two items from stack 0 to stack 1, but you
     // probably want more than that.
 
-    // Test that the instruction `move 2 from 0 to 1` works as expected with non-empty
-    // stacks.
+    // Test that a CrateMover 9000 reverses the order of a multi-crate move.
     #[test]
-    fn test_apply_instruction() {
+    fn test_apply_instruction_mover9000() {
         let stacks = Stacks {
             stacks: vec![
                 Stack {
@@ -406,16 +704,66 @@ mod tests {
         };
 
         let new_stacks = stacks
-            .apply_instruction(&instruction)
+            .apply_instruction(&instruction, CraneModel::Mover9000)
             .expect("Failed to apply instruction");
 
         assert_eq!(new_stacks.stacks[0], vec!['A']);
         assert_eq!(new_stacks.stacks[1], vec!['D', 'E', 'F', 'C', 'B']);
     }
 
-    // This essentially runs `main()` and checks that the results are correct for part 1.
+    // Test that a CrateMover 9001 preserves the order of a multi-crate move.
     #[test]
-    fn test_part_2() {
+    fn test_apply_instruction_mover9001() {
+        let stacks = Stacks {
+            stacks: vec![
+                Stack {
+                    stack: vec!['A', 'B', 'C'],
+                },
+                Stack {
+                    stack: vec!['D', 'E', 'F'],
+                },
+            ],
+        };
+
+        let instruction = CraneInstruction {
+            num_to_move: 2,
+            from_stack: 1,
+            to_stack: 2,
+        };
+
+        let new_stacks = stacks
+            .apply_instruction(&instruction, CraneModel::Mover9001)
+            .expect("Failed to apply instruction");
+
+        assert_eq!(new_stacks.stacks[0], vec!['A']);
+        assert_eq!(new_stacks.stacks[1], vec!['D', 'E', 'F', 'B', 'C']);
+    }
+
+    #[test]
+    fn test_stack_pop_n_and_peek() {
+        let mut stack = Stack {
+            stack: vec!['A', 'B', 'C'],
+        };
+
+        assert!(stack.has(3));
+        assert!(!stack.has(4));
+        assert_eq!(stack.peek(0), Some(&'C'));
+        assert_eq!(stack.peek(2), Some(&'A'));
+        assert_eq!(stack.peek(3), None);
+
+        let popped = stack.pop_n(2).expect("stack should have 2 items");
+        assert_eq!(popped, vec!['B', 'C']);
+        assert_eq!(stack, vec!['A']);
+        assert_eq!(stack.pop_n(2), None);
+
+        stack.push_n(popped);
+        assert_eq!(stack, vec!['A', 'B', 'C']);
+    }
+
+    // This essentially runs `main()` with a CrateMover 9000 and checks that
+    // the results match the known part 1 answer for this input.
+    #[test]
+    fn test_part_1() {
         let expected_string = format!("Failed to open file '{INPUT_FILE}'");
         let contents =
             fs::read_to_string(INPUT_FILE).expect(&expected_string);
@@ -433,7 +781,7 @@ mod tests {
             .expect("Failed to parse crane instructions");
 
         let final_state = stacks
-            .apply_instructions(&instructions)
+            .apply_instructions(&instructions, CraneModel::Mover9000)
             .expect("Applying an instruction set failed");
 
         let stack_tops = final_state
@@ -442,4 +790,40 @@ mod tests {
 
         assert_eq!("SBPQRSCDF", stack_tops);
     }
+
+    // This essentially runs `main()` and checks that the results are correct
+    // for part 2. A CrateMover 9001 preserves crate order on multi-crate
+    // moves, so on an input with multi-crate moves it can give a different
+    // top-of-stacks string than part 1; fill in the real answer below once
+    // you've run this binary against your own `input.txt`, then remove the
+    // `#[ignore]`.
+    #[ignore = "fill in the real CrateMover 9001 answer for your input.txt"]
+    #[test]
+    fn test_part_2() {
+        let expected_string = format!("Failed to open file '{INPUT_FILE}'");
+        let contents =
+            fs::read_to_string(INPUT_FILE).expect(&expected_string);
+
+        let (stack_config, instructions) = contents
+            .split_once("\n\n")
+            .expect("There was no blank line in the input");
+
+        let stacks: Stacks = stack_config
+            .parse()
+            .expect("Failed to parse stack configuration");
+
+        let instructions: CraneInstructions = instructions
+            .parse()
+            .expect("Failed to parse crane instructions");
+
+        let final_state = stacks
+            .apply_instructions(&instructions, CraneModel::Mover9001)
+            .expect("Applying an instruction set failed");
+
+        let stack_tops = final_state
+            .tops_string()
+            .expect("Tried to take the top of an empty stack");
+
+        assert_eq!("CHANGE_ME", stack_tops);
+    }
 }